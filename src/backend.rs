@@ -0,0 +1,131 @@
+//! Abstracts over the wire representation of a DynamoDB attribute so the
+//! (de)serialization layer in [`crate::ser`]/[`crate::de`] isn't hard-wired
+//! to one SDK's `AttributeValue` type.
+//!
+//! Enable the `rusoto` feature (on by default, for `rusoto_dynamodb`) and/or
+//! `aws-sdk` (for the official `aws-sdk-dynamodb`). Both can be enabled at
+//! once -- e.g. while migrating a service off rusoto -- since `to_fields`,
+//! `from_fields`, etc. are generic over whichever `AttributeValue` the call
+//! site's types point at.
+
+use std::collections::HashMap;
+
+/// A structural view of an attribute value, independent of which SDK
+/// produced it. Converting to/from this is the entire surface a new
+/// backend needs to implement.
+#[derive(Debug, Clone)]
+pub enum Repr<V> {
+    S(String),
+    N(String),
+    Bool(bool),
+    B(Vec<u8>),
+    Null,
+    L(Vec<V>),
+    M(HashMap<String, V>),
+    Ss(Vec<String>),
+    Ns(Vec<String>),
+    Bs(Vec<Vec<u8>>),
+    /// None of the backend's fields/variants were populated (or the
+    /// backend reported a variant this crate doesn't know about). Treated
+    /// as [`crate::error::DynamoDataError::UnknownAttributeValue`].
+    Unset,
+}
+
+/// Implemented by each supported SDK's `AttributeValue` type. `to_fields`,
+/// `from_fields`, `to_attribute_value`, and `from_attribute_value` are all
+/// generic over this trait, so the backend is picked by the type the
+/// caller's code already wants (a `rusoto_dynamodb::PutItemInput` field, an
+/// explicit type annotation, ...).
+pub trait AttributeValue: Sized + Clone {
+    fn into_repr(self) -> Repr<Self>;
+    fn from_repr(repr: Repr<Self>) -> Self;
+}
+
+#[cfg(feature = "rusoto")]
+impl AttributeValue for rusoto_dynamodb::AttributeValue {
+    fn into_repr(self) -> Repr<Self> {
+        if let Some(b) = self.b {
+            Repr::B(b.to_vec())
+        } else if let Some(v) = self.bool {
+            Repr::Bool(v)
+        } else if let Some(bs) = self.bs {
+            Repr::Bs(bs.into_iter().map(|b| b.to_vec()).collect())
+        } else if let Some(l) = self.l {
+            Repr::L(l)
+        } else if let Some(m) = self.m {
+            Repr::M(m)
+        } else if let Some(n) = self.n {
+            Repr::N(n)
+        } else if let Some(ns) = self.ns {
+            Repr::Ns(ns)
+        } else if let Some(s) = self.s {
+            Repr::S(s)
+        } else if self.null.is_some() {
+            Repr::Null
+        } else if let Some(ss) = self.ss {
+            Repr::Ss(ss)
+        } else {
+            Repr::Unset
+        }
+    }
+
+    fn from_repr(repr: Repr<Self>) -> Self {
+        match repr {
+            Repr::S(s) => Self { s: Some(s), ..Default::default() },
+            Repr::N(n) => Self { n: Some(n), ..Default::default() },
+            Repr::Bool(v) => Self { bool: Some(v), ..Default::default() },
+            Repr::B(b) => Self { b: Some(bytes::Bytes::from(b)), ..Default::default() },
+            Repr::Null => Self { null: Some(true), ..Default::default() },
+            Repr::L(l) => Self { l: Some(l), ..Default::default() },
+            Repr::M(m) => Self { m: Some(m), ..Default::default() },
+            Repr::Ss(ss) => Self { ss: Some(ss), ..Default::default() },
+            Repr::Ns(ns) => Self { ns: Some(ns), ..Default::default() },
+            Repr::Bs(bs) => Self {
+                bs: Some(bs.into_iter().map(bytes::Bytes::from).collect()),
+                ..Default::default()
+            },
+            Repr::Unset => Self::default(),
+        }
+    }
+}
+
+#[cfg(feature = "aws-sdk")]
+impl AttributeValue for aws_sdk_dynamodb::types::AttributeValue {
+    fn into_repr(self) -> Repr<Self> {
+        use aws_sdk_dynamodb::types::AttributeValue as Av;
+        match self {
+            Av::S(s) => Repr::S(s),
+            Av::N(n) => Repr::N(n),
+            Av::Bool(v) => Repr::Bool(v),
+            Av::B(b) => Repr::B(b.into_inner()),
+            Av::Null(_) => Repr::Null,
+            Av::L(l) => Repr::L(l),
+            Av::M(m) => Repr::M(m),
+            Av::Ss(ss) => Repr::Ss(ss),
+            Av::Ns(ns) => Repr::Ns(ns),
+            Av::Bs(bs) => Repr::Bs(bs.into_iter().map(|b| b.into_inner()).collect()),
+            // `AttributeValue` is `#[non_exhaustive]`; any future variant
+            // falls back to the same "couldn't make sense of this" path an
+            // empty rusoto `AttributeValue` takes.
+            _ => Repr::Unset,
+        }
+    }
+
+    fn from_repr(repr: Repr<Self>) -> Self {
+        use aws_sdk_dynamodb::types::AttributeValue as Av;
+        use aws_sdk_dynamodb::primitives::Blob;
+        match repr {
+            Repr::S(s) => Av::S(s),
+            Repr::N(n) => Av::N(n),
+            Repr::Bool(v) => Av::Bool(v),
+            Repr::B(b) => Av::B(Blob::new(b)),
+            Repr::Null => Av::Null(true),
+            Repr::L(l) => Av::L(l),
+            Repr::M(m) => Av::M(m),
+            Repr::Ss(ss) => Av::Ss(ss),
+            Repr::Ns(ns) => Av::Ns(ns),
+            Repr::Bs(bs) => Av::Bs(bs.into_iter().map(Blob::new).collect()),
+            Repr::Unset => Av::Null(true),
+        }
+    }
+}
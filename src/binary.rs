@@ -0,0 +1,49 @@
+//! Ergonomic support for DynamoDB's binary (`B`) attribute type.
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+/// A DynamoDB `B` attribute: an opaque byte blob (hashes, encrypted
+/// payloads, protobuf messages, ...) that should round-trip as-is instead
+/// of being interpreted as UTF-8 text or a list of numbers.
+///
+/// A plain `Vec<u8>` field serializes as an `L` of `N`s (that's what serde
+/// does with any sequence that isn't explicitly marked as bytes). Wrap it
+/// in `Binary` to get a real `B` attribute:
+///
+/// ```
+/// use dynamodb_data::Binary;
+/// let blob = Binary(vec![0xDE, 0xAD, 0xBE, 0xEF]);
+/// let value: rusoto_dynamodb::AttributeValue = dynamodb_data::to_attribute_value(&blob).expect("serde issue");
+/// assert!(value.b.is_some());
+/// let round_tripped: Binary = dynamodb_data::from_attribute_value(value).expect("serde issue");
+/// assert_eq!(round_tripped, blob);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Binary(pub Vec<u8>);
+
+impl Serialize for Binary {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_bytes::Bytes::new(&self.0).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Binary {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = serde_bytes::ByteBuf::deserialize(deserializer)?;
+        Ok(Binary(bytes.into_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_b() {
+        let blob = Binary(vec![0xDE, 0xAD, 0xBE, 0xEF]);
+        let value: rusoto_dynamodb::AttributeValue = crate::to_attribute_value(&blob).unwrap();
+        assert_eq!(value.b.as_deref(), Some([0xDE, 0xAD, 0xBE, 0xEF].as_slice()));
+        let round_tripped: Binary = crate::from_attribute_value(value).unwrap();
+        assert_eq!(round_tripped, blob);
+    }
+}
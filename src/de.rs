@@ -0,0 +1,234 @@
+//! A `serde::Deserializer` that reads directly from an [`AttributeValue`],
+//! dispatching on its [`Repr`] instead of converting to `serde_json::Value`
+//! first.
+//!
+//! Generic over [`AttributeValue`](crate::backend::AttributeValue) so the
+//! same deserializer drives any backend that implements it.
+
+use std::collections::HashMap;
+use serde::de::{self, Visitor, DeserializeOwned, DeserializeSeed, SeqAccess, MapAccess, IntoDeserializer, Error as _};
+use crate::backend::{AttributeValue, Repr};
+use crate::error::DynamoDataError;
+
+/// Deserializes `value` into any `DeserializeOwned` type.
+pub(crate) fn from_attribute_value<A: DeserializeOwned, Av: AttributeValue>(value: Av) -> Result<A, crate::error::DynamoDataError> {
+    A::deserialize(Deserializer(value))
+}
+
+pub(crate) struct Deserializer<Av>(pub(crate) Av);
+
+fn visit_number<'de, V: Visitor<'de>>(n: &str, visitor: V) -> Result<V::Value, crate::error::DynamoDataError> {
+    // Try the narrowest exact representation first, falling all the way
+    // back to `f64` only for values too big/precise for any integer type --
+    // this is what keeps large `i128`/`u128` ids from getting mangled into
+    // floats the way they would coming back out of `serde_json::Number`.
+    if let Ok(i) = n.parse::<i64>() {
+        visitor.visit_i64(i)
+    } else if let Ok(u) = n.parse::<u64>() {
+        visitor.visit_u64(u)
+    } else if let Ok(i) = n.parse::<i128>() {
+        visitor.visit_i128(i)
+    } else if let Ok(u) = n.parse::<u128>() {
+        visitor.visit_u128(u)
+    } else if let Ok(f) = n.parse::<f64>() {
+        visitor.visit_f64(f)
+    } else {
+        Err(DynamoDataError::InvalidNumber(n.to_owned()))
+    }
+}
+
+impl<'de, Av: AttributeValue> de::Deserializer<'de> for Deserializer<Av> {
+    type Error = crate::error::DynamoDataError;
+
+    fn deserialize_any<Vis: Visitor<'de>>(self, visitor: Vis) -> Result<Vis::Value, Self::Error> {
+        match self.0.into_repr() {
+            Repr::B(bytes) => visitor.visit_byte_buf(bytes),
+            Repr::Bool(v) => visitor.visit_bool(v),
+            Repr::Bs(bs) => {
+                let xs = bs.into_iter().map(|b| Av::from_repr(Repr::B(b))).collect();
+                visitor.visit_seq(SeqDeserializer::new(xs))
+            }
+            Repr::L(l) => visitor.visit_seq(SeqDeserializer::new(l)),
+            Repr::M(m) => visitor.visit_map(MapDeserializer::new(m)),
+            Repr::N(n) => visit_number(n.as_str(), visitor),
+            Repr::Ns(ns) => {
+                let xs = ns.into_iter().map(|n| Av::from_repr(Repr::S(n))).collect();
+                visitor.visit_seq(SeqDeserializer::new(xs))
+            }
+            Repr::S(s) => {
+                let text = if s == "\0" { String::new() } else { s };
+                visitor.visit_string(text)
+            }
+            Repr::Null => visitor.visit_unit(),
+            Repr::Ss(ss) => {
+                let xs = ss.into_iter().map(|s| Av::from_repr(Repr::S(s))).collect();
+                visitor.visit_seq(SeqDeserializer::new(xs))
+            }
+            Repr::Unset => Err(DynamoDataError::UnknownAttributeValue),
+        }
+    }
+
+    fn deserialize_option<Vis: Visitor<'de>>(self, visitor: Vis) -> Result<Vis::Value, Self::Error> {
+        match self.0.into_repr() {
+            Repr::Null => visitor.visit_none(),
+            repr => visitor.visit_some(Deserializer(Av::from_repr(repr))),
+        }
+    }
+
+    fn deserialize_newtype_struct<Vis: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: Vis,
+    ) -> Result<Vis::Value, Self::Error> {
+        use crate::sets::{STRING_SET_TOKEN, NUMBER_SET_TOKEN, BINARY_SET_TOKEN};
+
+        let repr = self.0.into_repr();
+        match (name, repr) {
+            (STRING_SET_TOKEN, Repr::Ss(xs)) => {
+                let l = xs.into_iter().map(|s| Av::from_repr(Repr::S(s))).collect();
+                visitor.visit_newtype_struct(Deserializer(Av::from_repr(Repr::L(l))))
+            }
+            (STRING_SET_TOKEN, _) => Err(DynamoDataError::custom("expected `SS` for StringSet")),
+            (NUMBER_SET_TOKEN, Repr::Ns(xs)) => {
+                let l = xs.into_iter().map(|n| Av::from_repr(Repr::S(n))).collect();
+                visitor.visit_newtype_struct(Deserializer(Av::from_repr(Repr::L(l))))
+            }
+            (NUMBER_SET_TOKEN, _) => Err(DynamoDataError::custom("expected `NS` for NumberSet")),
+            (BINARY_SET_TOKEN, Repr::Bs(xs)) => {
+                let l = xs.into_iter().map(|b| Av::from_repr(Repr::B(b))).collect();
+                visitor.visit_newtype_struct(Deserializer(Av::from_repr(Repr::L(l))))
+            }
+            (BINARY_SET_TOKEN, _) => Err(DynamoDataError::custom("expected `BS` for BinarySet")),
+            (_, repr) => visitor.visit_newtype_struct(Deserializer(Av::from_repr(repr))),
+        }
+    }
+
+    fn deserialize_enum<Vis: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: Vis,
+    ) -> Result<Vis::Value, Self::Error> {
+        match self.0.into_repr() {
+            Repr::S(s) => visitor.visit_enum(s.into_deserializer()),
+            repr => visitor.visit_enum(EnumDeserializer(Av::from_repr(repr))),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct SeqDeserializer<Av> {
+    iter: std::vec::IntoIter<Av>,
+}
+
+impl<Av> SeqDeserializer<Av> {
+    fn new(xs: Vec<Av>) -> Self {
+        SeqDeserializer { iter: xs.into_iter() }
+    }
+}
+
+impl<'de, Av: AttributeValue> SeqAccess<'de> for SeqDeserializer<Av> {
+    type Error = crate::error::DynamoDataError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        match self.iter.next() {
+            Some(value) => seed.deserialize(Deserializer(value)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        match self.iter.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(upper),
+            _ => None,
+        }
+    }
+}
+
+struct MapDeserializer<Av> {
+    iter: std::collections::hash_map::IntoIter<String, Av>,
+    value: Option<Av>,
+}
+
+impl<Av> MapDeserializer<Av> {
+    fn new(xs: HashMap<String, Av>) -> Self {
+        MapDeserializer { iter: xs.into_iter(), value: None }
+    }
+}
+
+impl<'de, Av: AttributeValue> MapAccess<'de> for MapDeserializer<Av> {
+    type Error = crate::error::DynamoDataError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<Seed: DeserializeSeed<'de>>(&mut self, seed: Seed) -> Result<Seed::Value, Self::Error> {
+        let value = self.value.take().expect("next_value_seed called before next_key_seed");
+        seed.deserialize(Deserializer(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        match self.iter.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(upper),
+            _ => None,
+        }
+    }
+}
+
+/// Handles enums represented as a single-entry `M` (the default, externally
+/// tagged shape the `Serializer` emits for newtype/tuple/struct variants).
+struct EnumDeserializer<Av>(Av);
+
+impl<'de, Av: AttributeValue> de::EnumAccess<'de> for EnumDeserializer<Av> {
+    type Error = crate::error::DynamoDataError;
+    type Variant = Deserializer<Av>;
+
+    fn variant_seed<Seed: DeserializeSeed<'de>>(self, seed: Seed) -> Result<(Seed::Value, Self::Variant), Self::Error> {
+        let mut m = match self.0.into_repr() {
+            Repr::M(m) => m,
+            _ => return Err(DynamoDataError::custom("expected `S` or single-entry `M` for enum")),
+        };
+        let key = m.keys().next().cloned()
+            .ok_or_else(|| DynamoDataError::custom("expected a non-empty `M` for enum"))?;
+        let value = m.remove(&key).expect("dynamodb attribute value: enum variant disappeared");
+        let variant = seed.deserialize(<String as IntoDeserializer<DynamoDataError>>::into_deserializer(key))?;
+        Ok((variant, Deserializer(value)))
+    }
+}
+
+impl<'de, Av: AttributeValue> de::VariantAccess<'de> for Deserializer<Av> {
+    type Error = crate::error::DynamoDataError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<Vis: Visitor<'de>>(self, _len: usize, visitor: Vis) -> Result<Vis::Value, Self::Error> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<Vis: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: Vis,
+    ) -> Result<Vis::Value, Self::Error> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
@@ -0,0 +1,84 @@
+//! The error type returned by this crate's (de)serialization functions.
+//!
+//! Previously, malformed or unrecognized `AttributeValue`s (an empty one, a
+//! non-UTF-8 binary blob, a malformed `N`) caused a bare `panic!()` deep
+//! inside the conversion layer, which meant one bad item from a table scan
+//! could take down the caller's whole task. `from_fields`/`from_attribute_value`
+//! now surface these as a recoverable `Result` instead.
+
+use std::fmt;
+use serde::{ser, de};
+
+/// Everything that can go wrong converting between Rust values and
+/// `AttributeValue`s.
+#[derive(Debug)]
+pub enum DynamoDataError {
+    /// The `AttributeValue` had none of its fields set, or none of the
+    /// fields this crate knows how to interpret.
+    UnknownAttributeValue,
+    /// An `N` value that didn't parse as a number. (`NS` elements are
+    /// handed on as strings and are not parsed.)
+    InvalidNumber(String),
+    /// Converted from a `FromUtf8Error`. The deserializer hands `B`/`BS`
+    /// values to visitors as raw bytes, so a non-UTF-8 blob read into a
+    /// `String` surfaces as `Serde`, not as this variant.
+    InvalidUtf8(std::string::FromUtf8Error),
+    /// The top-level value passed to `to_fields` did not serialize to a
+    /// struct/map, so it can't become a `HashMap<String, AttributeValue>`.
+    NotAnObject,
+    /// Any other (de)serialization failure: type mismatches, missing
+    /// fields, a custom `Serialize`/`Deserialize` impl's own error, etc.
+    Serde(serde_json::Error),
+}
+
+impl fmt::Display for DynamoDataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DynamoDataError::UnknownAttributeValue => {
+                write!(f, "empty or unrecognized AttributeValue")
+            }
+            DynamoDataError::InvalidNumber(n) => {
+                write!(f, "invalid DynamoDB `N` value: {:?}", n)
+            }
+            DynamoDataError::InvalidUtf8(e) => write!(f, "invalid UTF-8 in binary value: {}", e),
+            DynamoDataError::NotAnObject => {
+                write!(f, "expected a struct/map value, DynamoDB items must be objects")
+            }
+            DynamoDataError::Serde(e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+impl std::error::Error for DynamoDataError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DynamoDataError::InvalidUtf8(e) => Some(e),
+            DynamoDataError::Serde(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for DynamoDataError {
+    fn from(e: serde_json::Error) -> Self {
+        DynamoDataError::Serde(e)
+    }
+}
+
+impl From<std::string::FromUtf8Error> for DynamoDataError {
+    fn from(e: std::string::FromUtf8Error) -> Self {
+        DynamoDataError::InvalidUtf8(e)
+    }
+}
+
+impl ser::Error for DynamoDataError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DynamoDataError::Serde(<serde_json::Error as ser::Error>::custom(msg))
+    }
+}
+
+impl de::Error for DynamoDataError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DynamoDataError::Serde(<serde_json::Error as de::Error>::custom(msg))
+    }
+}
@@ -0,0 +1,206 @@
+//! An `UpdateExpression`/`ConditionExpression` builder that keeps
+//! `ExpressionAttributeNames`/`ExpressionAttributeValues` in sync as you go,
+//! instead of hand-assembling those three pieces and hoping they still
+//! agree.
+//!
+//! Every attribute name is aliased to a generated placeholder (`#n0`,
+//! `#n1`, ...) so reserved words (`name`, `status`, ...) are never a
+//! problem, and every value is aliased to a generated placeholder (`:v0`,
+//! `:v1`, ...) serialized via [`crate::to_attribute_value`].
+//!
+//! ```
+//! use dynamodb_data::Expression;
+//!
+//! let expr: dynamodb_data::BuiltExpression<rusoto_dynamodb::AttributeValue> = Expression::new()
+//!     .set("counter", 1).expect("serde issue")
+//!     .add("tags", dynamodb_data::StringSet(vec![String::from("vip")])).expect("serde issue")
+//!     .attribute_exists("id")
+//!     .build();
+//!
+//! rusoto_dynamodb::UpdateItemInput {
+//!     update_expression: expr.update_expression,
+//!     condition_expression: expr.condition_expression,
+//!     expression_attribute_names: expr.expression_attribute_names,
+//!     expression_attribute_values: expr.expression_attribute_values,
+//!     ..Default::default()
+//! };
+//! ```
+
+use std::collections::HashMap;
+use serde::Serialize;
+use crate::backend::AttributeValue;
+use crate::error::DynamoDataError;
+
+/// Builds an update/condition expression one clause at a time. See the
+/// [module docs](self) for an overview.
+pub struct Expression<V> {
+    sets: Vec<String>,
+    removes: Vec<String>,
+    adds: Vec<String>,
+    conditions: Vec<String>,
+    names: HashMap<String, String>,
+    values: HashMap<String, V>,
+}
+
+/// The pieces an `Expression` assembles into, named to match
+/// `UpdateItemInput`/`PutItemInput`'s own fields so they can be spread
+/// straight in.
+pub struct BuiltExpression<V> {
+    pub update_expression: Option<String>,
+    pub condition_expression: Option<String>,
+    pub expression_attribute_names: Option<HashMap<String, String>>,
+    pub expression_attribute_values: Option<HashMap<String, V>>,
+}
+
+impl<V: AttributeValue> Expression<V> {
+    pub fn new() -> Self {
+        Expression {
+            sets: Vec::new(),
+            removes: Vec::new(),
+            adds: Vec::new(),
+            conditions: Vec::new(),
+            names: HashMap::new(),
+            values: HashMap::new(),
+        }
+    }
+
+    /// `SET #nX = :vX` -- overwrite an attribute (the atomic-counter
+    /// pattern: `.set("counter", current + 1)`).
+    pub fn set<A: Serialize>(mut self, name: &str, value: A) -> Result<Self, DynamoDataError> {
+        let n = self.name_placeholder(name);
+        let v = self.value_placeholder(value)?;
+        self.sets.push(format!("{} = {}", n, v));
+        Ok(self)
+    }
+
+    /// `ADD #nX :vX` -- numeric increment, or union into a set attribute
+    /// (`StringSet`/`NumberSet`/`BinarySet`).
+    pub fn add<A: Serialize>(mut self, name: &str, value: A) -> Result<Self, DynamoDataError> {
+        let n = self.name_placeholder(name);
+        let v = self.value_placeholder(value)?;
+        self.adds.push(format!("{} {}", n, v));
+        Ok(self)
+    }
+
+    /// `REMOVE #nX` -- delete an attribute entirely.
+    pub fn remove(mut self, name: &str) -> Self {
+        let n = self.name_placeholder(name);
+        self.removes.push(n);
+        self
+    }
+
+    /// Condition clause: `attribute_exists(#nX)`.
+    pub fn attribute_exists(mut self, name: &str) -> Self {
+        let n = self.name_placeholder(name);
+        self.conditions.push(format!("attribute_exists({})", n));
+        self
+    }
+
+    /// Condition clause: `attribute_not_exists(#nX)` -- the usual guard for
+    /// a conditional put that must not overwrite an existing item.
+    pub fn attribute_not_exists(mut self, name: &str) -> Self {
+        let n = self.name_placeholder(name);
+        self.conditions.push(format!("attribute_not_exists({})", n));
+        self
+    }
+
+    /// Condition clause: `#nX = :vX`.
+    pub fn eq<A: Serialize>(mut self, name: &str, value: A) -> Result<Self, DynamoDataError> {
+        let n = self.name_placeholder(name);
+        let v = self.value_placeholder(value)?;
+        self.conditions.push(format!("{} = {}", n, v));
+        Ok(self)
+    }
+
+    /// Assembles the `SET`/`REMOVE`/`ADD` clauses and `AND`-joined
+    /// condition clauses collected so far into their final strings and
+    /// name/value maps.
+    pub fn build(self) -> BuiltExpression<V> {
+        let mut update_parts = Vec::new();
+        if !self.sets.is_empty() {
+            update_parts.push(format!("SET {}", self.sets.join(", ")));
+        }
+        if !self.removes.is_empty() {
+            update_parts.push(format!("REMOVE {}", self.removes.join(", ")));
+        }
+        if !self.adds.is_empty() {
+            update_parts.push(format!("ADD {}", self.adds.join(", ")));
+        }
+
+        BuiltExpression {
+            update_expression: if update_parts.is_empty() { None } else { Some(update_parts.join(" ")) },
+            condition_expression: if self.conditions.is_empty() { None } else { Some(self.conditions.join(" AND ")) },
+            expression_attribute_names: if self.names.is_empty() { None } else { Some(self.names) },
+            expression_attribute_values: if self.values.is_empty() { None } else { Some(self.values) },
+        }
+    }
+
+    /// Returns the existing placeholder for `name` if it was already
+    /// referenced, otherwise allocates and registers a new one (`#n0`,
+    /// `#n1`, ...).
+    fn name_placeholder(&mut self, name: &str) -> String {
+        if let Some((placeholder, _)) = self.names.iter().find(|(_, v)| v.as_str() == name) {
+            return placeholder.clone();
+        }
+        let placeholder = format!("#n{}", self.names.len());
+        self.names.insert(placeholder.clone(), name.to_owned());
+        placeholder
+    }
+
+    fn value_placeholder<A: Serialize>(&mut self, value: A) -> Result<String, DynamoDataError> {
+        let placeholder = format!(":v{}", self.values.len());
+        self.values.insert(placeholder.clone(), crate::to_attribute_value(value)?);
+        Ok(placeholder)
+    }
+}
+
+impl<V: AttributeValue> Default for Expression<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_condition_clauses_keep_names_and_values_in_sync() {
+        let built: BuiltExpression<rusoto_dynamodb::AttributeValue> = Expression::new()
+            .set("counter", 1).unwrap()
+            .attribute_exists("id")
+            .build();
+        assert_eq!(built.update_expression.as_deref(), Some("SET #n0 = :v0"));
+        assert_eq!(built.condition_expression.as_deref(), Some("attribute_exists(#n1)"));
+        assert_eq!(built.expression_attribute_names.unwrap().get("#n0").map(String::as_str), Some("counter"));
+    }
+
+    #[test]
+    fn repeated_name_reuses_its_placeholder() {
+        let built: BuiltExpression<rusoto_dynamodb::AttributeValue> = Expression::new()
+            .set("counter", 1).unwrap()
+            .eq("counter", 0).unwrap()
+            .build();
+        assert_eq!(built.update_expression.as_deref(), Some("SET #n0 = :v0"));
+        assert_eq!(built.condition_expression.as_deref(), Some("#n0 = :v1"));
+        assert_eq!(built.expression_attribute_names.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn remove_and_add_assemble_separate_update_clauses() {
+        let built: BuiltExpression<rusoto_dynamodb::AttributeValue> = Expression::new()
+            .remove("note")
+            .add("tags", crate::StringSet(vec![String::from("vip")])).unwrap()
+            .build();
+        assert_eq!(built.update_expression.as_deref(), Some("REMOVE #n0 ADD #n1 :v0"));
+    }
+
+    #[test]
+    fn empty_expression_builds_to_none() {
+        let built: BuiltExpression<rusoto_dynamodb::AttributeValue> = Expression::new().build();
+        assert!(built.update_expression.is_none());
+        assert!(built.condition_expression.is_none());
+        assert!(built.expression_attribute_names.is_none());
+        assert!(built.expression_attribute_values.is_none());
+    }
+}
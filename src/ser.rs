@@ -0,0 +1,436 @@
+//! A `serde::Serializer` that writes directly to an [`AttributeValue`], without
+//! bouncing through `serde_json::Value` first.
+//!
+//! Going straight to `AttributeValue` means integers/decimals keep their own
+//! `Display` representation instead of being re-parsed as `serde_json::Number`
+//! (which silently loses precision on large `i64`/`u128`/`f64` values), and it
+//! gives later stages (binary, sets) a place to hook in without detouring
+//! through JSON semantics that don't exist in DynamoDB.
+//!
+//! Generic over [`AttributeValue`](crate::backend::AttributeValue) so the
+//! same serializer drives any backend that implements it.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::marker::PhantomData;
+use serde::Serialize;
+use serde::ser::{self, Error as _};
+use crate::backend::{AttributeValue, Repr};
+
+/// Serializes `value` straight into an `AttributeValue` tree.
+pub(crate) fn to_attribute_value<A: Serialize, V: AttributeValue>(value: A) -> Result<V, crate::error::DynamoDataError> {
+    value.serialize(Serializer::<V>::default())
+}
+
+pub(crate) struct Serializer<V>(PhantomData<V>);
+
+impl<V> Clone for Serializer<V> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<V> Copy for Serializer<V> {}
+
+impl<V> Default for Serializer<V> {
+    fn default() -> Self { Serializer(PhantomData) }
+}
+
+fn number<V: AttributeValue, N: Display>(n: N) -> V {
+    V::from_repr(Repr::N(n.to_string()))
+}
+
+fn string<V: AttributeValue>(value: String) -> V {
+    // DynamoDB (historically) rejects empty `S` values, so we round-trip
+    // empty strings through a sentinel, same as before.
+    if value.is_empty() {
+        V::from_repr(Repr::S(String::from("\0")))
+    } else {
+        V::from_repr(Repr::S(value))
+    }
+}
+
+impl<V: AttributeValue> ser::Serializer for Serializer<V> {
+    type Ok = V;
+    type Error = crate::error::DynamoDataError;
+
+    type SerializeSeq = SerializeVec<V>;
+    type SerializeTuple = SerializeVec<V>;
+    type SerializeTupleStruct = SerializeVec<V>;
+    type SerializeTupleVariant = SerializeTupleVariant<V>;
+    type SerializeMap = SerializeMap<V>;
+    type SerializeStruct = SerializeMap<V>;
+    type SerializeStructVariant = SerializeStructVariant<V>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        Ok(V::from_repr(Repr::Bool(v)))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> { Ok(number(v)) }
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> { Ok(number(v)) }
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> { Ok(number(v)) }
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> { Ok(number(v)) }
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> { Ok(number(v)) }
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> { Ok(number(v)) }
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> { Ok(number(v)) }
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> { Ok(number(v)) }
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> { Ok(number(v)) }
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> { Ok(number(v)) }
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> { Ok(number(v)) }
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> { Ok(number(v)) }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        Ok(string(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(string(v.to_owned()))
+    }
+
+    /// Only reached via `serde_bytes` (e.g. the `Binary` newtype, or
+    /// `#[serde(with = "serde_bytes")]`) -- a plain `Vec<u8>`/`&[u8]` field
+    /// has no such annotation and still serializes as an `L` of `N`s, since
+    /// that's what an unannotated sequence of `u8` looks like to serde.
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Ok(V::from_repr(Repr::B(v.to_vec())))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(V::from_repr(Repr::Null))
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(V::from_repr(Repr::Null))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(string(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        use crate::sets::{STRING_SET_TOKEN, NUMBER_SET_TOKEN, BINARY_SET_TOKEN};
+
+        match name {
+            STRING_SET_TOKEN => {
+                let l = match value.serialize(self)?.into_repr() {
+                    Repr::L(l) => l,
+                    _ => return Err(Self::Error::custom("StringSet must serialize its inner Vec<String> as a list")),
+                };
+                let xs = l.into_iter().map(|x| match x.into_repr() {
+                    Repr::S(s) => Ok(s),
+                    _ => Err(Self::Error::custom("StringSet elements must be strings")),
+                }).collect::<Result<_, _>>()?;
+                Ok(V::from_repr(Repr::Ss(xs)))
+            }
+            NUMBER_SET_TOKEN => {
+                let l = match value.serialize(self)?.into_repr() {
+                    Repr::L(l) => l,
+                    _ => return Err(Self::Error::custom("NumberSet must serialize its inner Vec<String> as a list")),
+                };
+                let xs = l.into_iter().map(|x| match x.into_repr() {
+                    Repr::S(s) => Ok(s),
+                    _ => Err(Self::Error::custom("NumberSet elements must be strings")),
+                }).collect::<Result<_, _>>()?;
+                Ok(V::from_repr(Repr::Ns(xs)))
+            }
+            BINARY_SET_TOKEN => {
+                let l = match value.serialize(self)?.into_repr() {
+                    Repr::L(l) => l,
+                    _ => return Err(Self::Error::custom("BinarySet must serialize its inner Vec<ByteBuf> as a list")),
+                };
+                let xs = l.into_iter().map(|x| match x.into_repr() {
+                    Repr::B(b) => Ok(b),
+                    _ => Err(Self::Error::custom("BinarySet elements must be binary")),
+                }).collect::<Result<_, _>>()?;
+                Ok(V::from_repr(Repr::Bs(xs)))
+            }
+            _ => value.serialize(self),
+        }
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        let mut m = HashMap::with_capacity(1);
+        m.insert(variant.to_owned(), value.serialize(self)?);
+        Ok(V::from_repr(Repr::M(m)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SerializeVec { xs: Vec::with_capacity(len.unwrap_or(0)) })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(SerializeTupleVariant {
+            variant,
+            xs: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(SerializeMap { xs: HashMap::new(), next_key: None })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(SerializeMap { xs: HashMap::with_capacity(len), next_key: None })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(SerializeStructVariant {
+            variant,
+            xs: HashMap::with_capacity(len),
+        })
+    }
+
+    fn collect_str<T: ?Sized + Display>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        Ok(string(value.to_string()))
+    }
+}
+
+pub(crate) struct SerializeVec<V> {
+    xs: Vec<V>,
+}
+
+impl<V: AttributeValue> ser::SerializeSeq for SerializeVec<V> {
+    type Ok = V;
+    type Error = crate::error::DynamoDataError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.xs.push(value.serialize(Serializer::<V>::default())?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(V::from_repr(Repr::L(self.xs)))
+    }
+}
+
+impl<V: AttributeValue> ser::SerializeTuple for SerializeVec<V> {
+    type Ok = V;
+    type Error = crate::error::DynamoDataError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl<V: AttributeValue> ser::SerializeTupleStruct for SerializeVec<V> {
+    type Ok = V;
+    type Error = crate::error::DynamoDataError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+pub(crate) struct SerializeTupleVariant<V> {
+    variant: &'static str,
+    xs: Vec<V>,
+}
+
+impl<V: AttributeValue> ser::SerializeTupleVariant for SerializeTupleVariant<V> {
+    type Ok = V;
+    type Error = crate::error::DynamoDataError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.xs.push(value.serialize(Serializer::<V>::default())?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        let mut m = HashMap::with_capacity(1);
+        m.insert(self.variant.to_owned(), V::from_repr(Repr::L(self.xs)));
+        Ok(V::from_repr(Repr::M(m)))
+    }
+}
+
+pub(crate) struct SerializeMap<V> {
+    xs: HashMap<String, V>,
+    next_key: Option<String>,
+}
+
+impl<V: AttributeValue> ser::SerializeMap for SerializeMap<V> {
+    type Ok = V;
+    type Error = crate::error::DynamoDataError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        let key = key.serialize(MapKeySerializer)?;
+        self.next_key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self.next_key.take().expect("serialize_value called before serialize_key");
+        self.xs.insert(key, value.serialize(Serializer::<V>::default())?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(V::from_repr(Repr::M(self.xs)))
+    }
+}
+
+impl<V: AttributeValue> ser::SerializeStruct for SerializeMap<V> {
+    type Ok = V;
+    type Error = crate::error::DynamoDataError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.xs.insert(key.to_owned(), value.serialize(Serializer::<V>::default())?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(V::from_repr(Repr::M(self.xs)))
+    }
+}
+
+pub(crate) struct SerializeStructVariant<V> {
+    variant: &'static str,
+    xs: HashMap<String, V>,
+}
+
+impl<V: AttributeValue> ser::SerializeStructVariant for SerializeStructVariant<V> {
+    type Ok = V;
+    type Error = crate::error::DynamoDataError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.xs.insert(key.to_owned(), value.serialize(Serializer::<V>::default())?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        let mut m = HashMap::with_capacity(1);
+        m.insert(self.variant.to_owned(), V::from_repr(Repr::M(self.xs)));
+        Ok(V::from_repr(Repr::M(m)))
+    }
+}
+
+/// Map/struct keys have to come back as plain `String`s (DynamoDB attribute
+/// names), not nested `AttributeValue`s.
+struct MapKeySerializer;
+
+impl ser::Serializer for MapKeySerializer {
+    type Ok = String;
+    type Error = crate::error::DynamoDataError;
+
+    type SerializeSeq = ser::Impossible<String, crate::error::DynamoDataError>;
+    type SerializeTuple = ser::Impossible<String, crate::error::DynamoDataError>;
+    type SerializeTupleStruct = ser::Impossible<String, crate::error::DynamoDataError>;
+    type SerializeTupleVariant = ser::Impossible<String, crate::error::DynamoDataError>;
+    type SerializeMap = ser::Impossible<String, crate::error::DynamoDataError>;
+    type SerializeStruct = ser::Impossible<String, crate::error::DynamoDataError>;
+    type SerializeStructVariant = ser::Impossible<String, crate::error::DynamoDataError>;
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(v.to_owned())
+    }
+
+    fn collect_str<T: ?Sized + Display>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> { Ok(v.to_string()) }
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> { Ok(v.to_string()) }
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> { Ok(v.to_string()) }
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> { Ok(v.to_string()) }
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> { Ok(v.to_string()) }
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> { Ok(v.to_string()) }
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> { Ok(v.to_string()) }
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> { Ok(v.to_string()) }
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> { Ok(v.to_string()) }
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> { value.serialize(self) }
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+    fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str) -> Result<Self::Ok, Self::Error> { Ok(variant.to_owned()) }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Result<Self::Ok, Self::Error> { value.serialize(self) }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _name: &'static str, _variant_index: u32, _variant: &'static str, _value: &T) -> Result<Self::Ok, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+    fn serialize_tuple_variant(self, _name: &'static str, _variant_index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeTupleVariant, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+    fn serialize_struct_variant(self, _name: &'static str, _variant_index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeStructVariant, Self::Error> { Err(Self::Error::custom("field names must be strings")) }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn large_i128_and_u128_round_trip_without_precision_loss() {
+        let min: rusoto_dynamodb::AttributeValue = crate::to_attribute_value(i128::MIN).unwrap();
+        assert_eq!(min.n.as_deref(), Some("-170141183460469231731687303715884105728"));
+        assert_eq!(crate::from_attribute_value::<i128, _>(min).unwrap(), i128::MIN);
+
+        let max: rusoto_dynamodb::AttributeValue = crate::to_attribute_value(u128::MAX).unwrap();
+        assert_eq!(max.n.as_deref(), Some("340282366920938463463374607431768211455"));
+        assert_eq!(crate::from_attribute_value::<u128, _>(max).unwrap(), u128::MAX);
+    }
+}
@@ -0,0 +1,143 @@
+//! Native DynamoDB Set types: String Set (`SS`), Number Set (`NS`), and
+//! Binary Set (`BS`).
+//!
+//! Without these, every Rust collection (`Vec`, `HashSet`, ...) serializes
+//! as an `L`, which means DynamoDB's atomic `ADD`/`DELETE` update-expression
+//! operations on sets are unreachable from this crate. Wrap the field in
+//! one of these newtypes to get the real attribute type instead.
+
+use std::fmt;
+use std::marker::PhantomData;
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::Visitor;
+use serde_bytes::ByteBuf;
+
+pub(crate) const STRING_SET_TOKEN: &str = "$__dynamodb_data::StringSet";
+pub(crate) const NUMBER_SET_TOKEN: &str = "$__dynamodb_data::NumberSet";
+pub(crate) const BINARY_SET_TOKEN: &str = "$__dynamodb_data::BinarySet";
+
+/// A DynamoDB String Set (`SS`) attribute.
+///
+/// ```
+/// use dynamodb_data::StringSet;
+/// let tags = StringSet(vec![String::from("a"), String::from("b")]);
+/// let value: rusoto_dynamodb::AttributeValue = dynamodb_data::to_attribute_value(&tags).expect("serde issue");
+/// assert!(value.ss.is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StringSet(pub Vec<String>);
+
+/// A DynamoDB Number Set (`NS`) attribute. Elements are kept as the raw
+/// numeric string DynamoDB stores them as, same as `N` elsewhere in this
+/// crate, so arbitrary precision is preserved.
+///
+/// ```
+/// use dynamodb_data::NumberSet;
+/// let ids = NumberSet(vec![String::from("1"), String::from("2")]);
+/// let value: rusoto_dynamodb::AttributeValue = dynamodb_data::to_attribute_value(&ids).expect("serde issue");
+/// assert!(value.ns.is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NumberSet(pub Vec<String>);
+
+/// A DynamoDB Binary Set (`BS`) attribute.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BinarySet(pub Vec<Vec<u8>>);
+
+impl Serialize for StringSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(STRING_SET_TOKEN, &self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for StringSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_newtype_struct(STRING_SET_TOKEN, NewtypeVisitor::<Vec<String>>::new())
+            .map(StringSet)
+    }
+}
+
+impl Serialize for NumberSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(NUMBER_SET_TOKEN, &self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for NumberSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_newtype_struct(NUMBER_SET_TOKEN, NewtypeVisitor::<Vec<String>>::new())
+            .map(NumberSet)
+    }
+}
+
+impl Serialize for BinarySet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bufs: Vec<ByteBuf> = self.0.iter().cloned().map(ByteBuf::from).collect();
+        serializer.serialize_newtype_struct(BINARY_SET_TOKEN, &bufs)
+    }
+}
+
+impl<'de> Deserialize<'de> for BinarySet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bufs = deserializer
+            .deserialize_newtype_struct(BINARY_SET_TOKEN, NewtypeVisitor::<Vec<ByteBuf>>::new())?;
+        Ok(BinarySet(bufs.into_iter().map(ByteBuf::into_vec).collect()))
+    }
+}
+
+/// Forwards a newtype-struct deserialize call straight to `T`'s own
+/// `Deserialize` impl, so the set wrappers above don't need bespoke
+/// `Visitor`s of their own.
+struct NewtypeVisitor<T>(PhantomData<T>);
+
+impl<T> NewtypeVisitor<T> {
+    fn new() -> Self {
+        NewtypeVisitor(PhantomData)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for NewtypeVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a DynamoDB set attribute")
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        T::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_set_round_trips_through_ss() {
+        let tags = StringSet(vec![String::from("a"), String::from("b")]);
+        let value: rusoto_dynamodb::AttributeValue = crate::to_attribute_value(&tags).unwrap();
+        assert_eq!(value.ss, Some(vec![String::from("a"), String::from("b")]));
+        let round_tripped: StringSet = crate::from_attribute_value(value).unwrap();
+        assert_eq!(round_tripped, tags);
+    }
+
+    #[test]
+    fn number_set_round_trips_through_ns() {
+        let ids = NumberSet(vec![String::from("1"), String::from("2")]);
+        let value: rusoto_dynamodb::AttributeValue = crate::to_attribute_value(&ids).unwrap();
+        assert_eq!(value.ns, Some(vec![String::from("1"), String::from("2")]));
+        let round_tripped: NumberSet = crate::from_attribute_value(value).unwrap();
+        assert_eq!(round_tripped, ids);
+    }
+
+    #[test]
+    fn binary_set_round_trips_through_bs() {
+        let blobs = BinarySet(vec![vec![0xDE, 0xAD], vec![0xBE, 0xEF]]);
+        let value: rusoto_dynamodb::AttributeValue = crate::to_attribute_value(&blobs).unwrap();
+        assert_eq!(value.bs.as_ref().map(|bs| bs.len()), Some(2));
+        let round_tripped: BinarySet = crate::from_attribute_value(value).unwrap();
+        assert_eq!(round_tripped, blobs);
+    }
+}
@@ -0,0 +1,103 @@
+//! Typed decoding for DynamoDB Streams records.
+//!
+//! A stream record's `NewImage`/`OldImage` are the same
+//! `HashMap<String, AttributeValue>` shape `from_fields` already knows how
+//! to decode -- this is a thin typed layer over that, plus the event-name
+//! enum, so a Lambda/Kinesis stream processor doesn't have to hand-roll it.
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use dynamodb_data::{from_stream_record, StreamEventName};
+//!
+//! #[derive(serde::Deserialize)]
+//! struct Item { id: String, counter: i64 }
+//!
+//! let new_image: HashMap<String, rusoto_dynamodb::AttributeValue> = dynamodb_data::fields!{
+//!     id => "abc",
+//!     counter => 1
+//! };
+//! let change: dynamodb_data::StreamChange<Item> = from_stream_record(
+//!     StreamEventName::Insert,
+//!     None,
+//!     Some(new_image),
+//! ).expect("serde issue");
+//! assert!(change.old.is_none());
+//! assert_eq!(change.new.unwrap().counter, 1);
+//! ```
+
+use std::collections::HashMap;
+use serde::de::DeserializeOwned;
+use crate::backend::AttributeValue;
+use crate::error::DynamoDataError;
+
+/// The `eventName` of a DynamoDB Streams record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamEventName {
+    Insert,
+    Modify,
+    Remove,
+}
+
+/// A stream record decoded into `T`. Exactly which of `old`/`new` is
+/// populated depends on `event` and on the stream's `StreamViewType`
+/// (`OLD_IMAGE`/`NEW_IMAGE` streams only ever populate one side).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamChange<T> {
+    pub event: StreamEventName,
+    pub old: Option<T>,
+    pub new: Option<T>,
+}
+
+/// Decodes a stream record's `OldImage`/`NewImage` into `T`, reusing
+/// [`crate::from_fields`] for each side that's present.
+pub fn from_stream_record<T: DeserializeOwned, V: AttributeValue>(
+    event: StreamEventName,
+    old_image: Option<HashMap<String, V>>,
+    new_image: Option<HashMap<String, V>>,
+) -> Result<StreamChange<T>, DynamoDataError> {
+    Ok(StreamChange {
+        event,
+        old: old_image.map(crate::from_fields).transpose()?,
+        new: new_image.map(crate::from_fields).transpose()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Item {
+        id: String,
+        counter: i64,
+    }
+
+    fn image(counter: i64) -> HashMap<String, rusoto_dynamodb::AttributeValue> {
+        crate::fields! { id => "abc", counter => counter }
+    }
+
+    #[test]
+    fn insert_only_populates_new() {
+        let change: StreamChange<Item> =
+            from_stream_record(StreamEventName::Insert, None, Some(image(1))).unwrap();
+        assert!(change.old.is_none());
+        assert_eq!(change.new, Some(Item { id: String::from("abc"), counter: 1 }));
+    }
+
+    #[test]
+    fn modify_populates_both_old_and_new() {
+        let change: StreamChange<Item> =
+            from_stream_record(StreamEventName::Modify, Some(image(1)), Some(image(2))).unwrap();
+        assert_eq!(change.old, Some(Item { id: String::from("abc"), counter: 1 }));
+        assert_eq!(change.new, Some(Item { id: String::from("abc"), counter: 2 }));
+    }
+
+    #[test]
+    fn remove_only_populates_old() {
+        let change: StreamChange<Item> =
+            from_stream_record(StreamEventName::Remove, Some(image(1)), None).unwrap();
+        assert_eq!(change.old, Some(Item { id: String::from("abc"), counter: 1 }));
+        assert!(change.new.is_none());
+    }
+}